@@ -0,0 +1,222 @@
+use num::Complex;
+use rand::Rng;
+
+/// Accumulate orbit density for the Buddhabrot rendering mode.
+///
+/// `samples` complex points `c` are drawn uniformly from a region slightly
+/// larger than `upper_left`..`lower_right`. For each sample, the usual
+/// Mandelbrot iteration `z = z*z + c` is run up to `limit` steps; orbits
+/// that never escape are discarded, since they contribute nothing to the
+/// Buddhabrot. Orbits that do escape are replayed from `z = 0`, and each
+/// intermediate `z_n` that lands within `bounds` has its pixel's histogram
+/// cell incremented.
+///
+/// The work is split across `threads` crossbeam threads, each of which
+/// accumulates into its own local histogram over the full image to avoid
+/// contention, before the histograms are summed.
+pub fn accumulate(
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    samples: u32,
+    limit: u32,
+    threads: usize,
+) -> Vec<u32> {
+    let threads = threads.max(1);
+
+    let margin = 0.2;
+    let width = lower_right.re - upper_left.re;
+    let height = upper_left.im - lower_right.im;
+    let sample_upper_left = Complex {
+        re: upper_left.re - width * margin,
+        im: upper_left.im + height * margin,
+    };
+    let sample_lower_right = Complex {
+        re: lower_right.re + width * margin,
+        im: lower_right.im - height * margin,
+    };
+
+    let samples_per_thread = samples / threads as u32;
+    let remainder = samples % threads as u32;
+
+    let histograms: Vec<Vec<u32>> = crossbeam::scope(|spawner| {
+        let mut handles = Vec::with_capacity(threads);
+        for i in 0..threads {
+            let thread_samples = samples_per_thread + if (i as u32) < remainder { 1 } else { 0 };
+            handles.push(spawner.spawn(move || {
+                accumulate_locally(
+                    bounds,
+                    upper_left,
+                    lower_right,
+                    sample_upper_left,
+                    sample_lower_right,
+                    thread_samples,
+                    limit,
+                )
+            }));
+        }
+        handles.into_iter().map(|handle| handle.join()).collect()
+    });
+
+    let mut total = vec![0u32; bounds.0 * bounds.1];
+    for histogram in histograms {
+        for (cell, count) in total.iter_mut().zip(histogram) {
+            *cell += count;
+        }
+    }
+    total
+}
+
+/// Draw `samples` orbits, sampling `c` from the margin-expanded
+/// `sample_upper_left`..`sample_lower_right` rectangle but mapping escaping
+/// orbit points onto pixels using the original (unexpanded) `upper_left`..
+/// `lower_right` viewport, accumulating into a histogram local to the
+/// calling thread.
+fn accumulate_locally(
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    sample_upper_left: Complex<f64>,
+    sample_lower_right: Complex<f64>,
+    samples: u32,
+    limit: u32,
+) -> Vec<u32> {
+    let mut histogram = vec![0u32; bounds.0 * bounds.1];
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..samples {
+        let c = Complex {
+            re: rng.gen_range(sample_upper_left.re, sample_lower_right.re),
+            im: rng.gen_range(sample_lower_right.im, sample_upper_left.im),
+        };
+
+        if !escapes(c, limit) {
+            continue;
+        }
+
+        let mut z = Complex { re: 0.0, im: 0.0 };
+        for _ in 0..limit {
+            z = z * z + c;
+            if z.norm_sqr() > 4.0 {
+                break;
+            }
+            if let Some((column, row)) = super::point_to_pixel(bounds, z, upper_left, lower_right) {
+                histogram[row * bounds.0 + column] += 1;
+            }
+        }
+    }
+
+    histogram
+}
+
+/// Whether the orbit of `c` under `z = z*z + c` escapes within `limit` iterations.
+fn escapes(c: Complex<f64>, limit: u32) -> bool {
+    let mut z = Complex { re: 0.0, im: 0.0 };
+    for _ in 0..limit {
+        z = z * z + c;
+        if z.norm_sqr() > 4.0 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Map a histogram of orbit-density counts to grayscale brightness, applying
+/// a `sqrt` gamma so that sparse, high-dynamic-range cells stay visible.
+pub fn histogram_to_pixels(histogram: &[u32]) -> Vec<u8> {
+    let max = histogram.iter().cloned().max().unwrap_or(0).max(1) as f64;
+    histogram
+        .iter()
+        .map(|&count| ((count as f64 / max).sqrt() * 255.0) as u8)
+        .collect()
+}
+
+#[test]
+fn test_escapes() {
+    assert!(escapes(Complex { re: 2.0, im: 2.0 }, 50));
+    assert!(!escapes(Complex { re: 0.0, im: 0.0 }, 50));
+}
+
+#[test]
+fn test_histogram_to_pixels_max_is_full_brightness() {
+    let histogram = vec![0, 3, 1, 0];
+    assert_eq!(histogram_to_pixels(&histogram), vec![0, 255, 147, 0]);
+}
+
+#[test]
+fn test_histogram_to_pixels_empty_is_black() {
+    assert_eq!(histogram_to_pixels(&[0, 0, 0]), vec![0, 0, 0]);
+}
+
+#[test]
+fn test_accumulate_locally_interior_contributes_nothing() {
+    let bounds = (10, 10);
+    let upper_left = Complex {
+        re: -0.01,
+        im: 0.01,
+    };
+    let lower_right = Complex {
+        re: 0.01,
+        im: -0.01,
+    };
+    let histogram = accumulate_locally(
+        bounds,
+        upper_left,
+        lower_right,
+        upper_left,
+        lower_right,
+        200,
+        100,
+    );
+    assert!(histogram.iter().all(|&count| count == 0));
+}
+
+#[test]
+fn test_accumulate_locally_escaping_region_contributes() {
+    let bounds = (50, 50);
+    let upper_left = Complex { re: -2.0, im: 2.0 };
+    let lower_right = Complex { re: 2.0, im: -2.0 };
+    let histogram = accumulate_locally(
+        bounds,
+        upper_left,
+        lower_right,
+        upper_left,
+        lower_right,
+        2000,
+        50,
+    );
+    assert!(histogram.iter().any(|&count| count > 0));
+}
+
+#[test]
+fn test_accumulate_locally_maps_onto_view_not_sample_region() {
+    // Pin `c` to (approximately) 0.5+0.5i with a vanishingly narrow sampling
+    // rectangle. The orbit's first iterate is `c` itself (inside the sampling
+    // rectangle) and its second is about 0.5+1.0i (inside `view` but nowhere
+    // near the sampling rectangle), before it leaves `view` and escapes. Both
+    // of those land in distinct cells of `view`. If orbit points were
+    // (incorrectly) mapped to pixels using the sampling rectangle instead of
+    // `view`, only the first iterate would hit, leaving at most one cell set.
+    let bounds = (10, 10);
+    let view_upper_left = Complex { re: 0.0, im: 1.2 };
+    let view_lower_right = Complex { re: 1.0, im: -1.2 };
+    let sample_upper_left = Complex {
+        re: 0.5,
+        im: 0.5 + 1e-9,
+    };
+    let sample_lower_right = Complex {
+        re: 0.5 + 1e-9,
+        im: 0.5,
+    };
+
+    let histogram = accumulate_locally(
+        bounds,
+        view_upper_left,
+        view_lower_right,
+        sample_upper_left,
+        sample_lower_right,
+        1,
+        10,
+    );
+    assert!(histogram.iter().filter(|&&count| count > 0).count() >= 2);
+}
@@ -0,0 +1,120 @@
+use clap::{Args, Parser, Subcommand};
+use num::Complex;
+
+use crate::fractal::FractalKind;
+use crate::image_format::OutputFormat;
+use crate::palette::Palette;
+use crate::parse_complex;
+use crate::parse_pair;
+use crate::parse_triple;
+
+/// Render Mandelbrot-family escape-time fractals and Buddhabrot orbit-density images.
+#[derive(Parser)]
+#[command(name = "mandelbrot", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub mode: Mode,
+}
+
+#[derive(Subcommand)]
+pub enum Mode {
+    /// Render an escape-time fractal (Mandelbrot, Multibrot, Burning Ship, Tricorn).
+    Render(RenderArgs),
+    /// Render a Buddhabrot orbit-density image.
+    Buddhabrot(BuddhabrotArgs),
+}
+
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Output image file.
+    pub file: String,
+
+    /// Image dimensions, e.g. "1000x750".
+    #[arg(long, default_value = "1000x750", value_parser = parse_dimensions)]
+    pub dimensions: (usize, usize),
+
+    /// Complex-plane region to render, e.g. "-1.20,0.35x-1.0,0.20".
+    #[arg(long, default_value = "-1.20,0.35x-1.0,0.20", value_parser = parse_region)]
+    pub region: (Complex<f64>, Complex<f64>),
+
+    /// Maximum number of escape-time iterations per pixel.
+    #[arg(long = "max-iterations", default_value_t = 255)]
+    pub max_iterations: u32,
+
+    /// Number of worker threads to render with.
+    #[arg(long, default_value_t = 8)]
+    pub threads: usize,
+
+    /// Fractal family to render: mandelbrot, burningship, tricorn, or multibrot:<degree>.
+    #[arg(long, default_value = "mandelbrot")]
+    pub fractal: FractalKind,
+
+    /// Coloring scheme: gaussian, hsl, fire, or ocean.
+    #[arg(long, default_value = "gaussian")]
+    pub palette: Palette,
+
+    /// Use continuous (smooth) coloring instead of integer iteration counts.
+    #[arg(long)]
+    pub smooth: bool,
+
+    /// Color for points that never escape, as "R,G,B".
+    #[arg(long = "interior-color", default_value = "40,40,40", value_parser = parse_interior_color)]
+    pub interior_color: (u8, u8, u8),
+
+    /// Output image format: png, ppm, pgm, or bmp. Inferred from the
+    /// output file's extension when not given.
+    #[arg(long)]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Args)]
+pub struct BuddhabrotArgs {
+    /// Output image file.
+    pub file: String,
+
+    /// Image dimensions, e.g. "1000x750".
+    #[arg(long, default_value = "1000x750", value_parser = parse_dimensions)]
+    pub dimensions: (usize, usize),
+
+    /// Complex-plane region to render, e.g. "-1.20,0.35x-1.0,0.20".
+    #[arg(long, default_value = "-1.20,0.35x-1.0,0.20", value_parser = parse_region)]
+    pub region: (Complex<f64>, Complex<f64>),
+
+    /// Number of random orbit samples to draw.
+    #[arg(long, default_value_t = 5_000_000)]
+    pub samples: u32,
+
+    /// Maximum number of iterations per orbit.
+    #[arg(long = "max-iterations", default_value_t = 500)]
+    pub max_iterations: u32,
+
+    /// Number of worker threads to render with.
+    #[arg(long, default_value_t = 8)]
+    pub threads: usize,
+
+    /// Output image format: png, ppm, pgm, or bmp. Inferred from the
+    /// output file's extension when not given.
+    #[arg(long)]
+    pub format: Option<OutputFormat>,
+}
+
+fn parse_dimensions(s: &str) -> Result<(usize, usize), String> {
+    parse_pair(s, 'x').ok_or_else(|| format!("invalid dimensions: '{}'", s))
+}
+
+/// Parse a region string like `"-1.20,0.35x-1.0,0.20"` into its upper-left
+/// and lower-right corners.
+fn parse_region(s: &str) -> Result<(Complex<f64>, Complex<f64>), String> {
+    let index = s
+        .find('x')
+        .ok_or_else(|| format!("invalid region: '{}'", s))?;
+    let upper_left =
+        parse_complex(&s[..index]).ok_or_else(|| format!("invalid region: '{}'", s))?;
+    let lower_right =
+        parse_complex(&s[index + 1..]).ok_or_else(|| format!("invalid region: '{}'", s))?;
+    Ok((upper_left, lower_right))
+}
+
+fn parse_interior_color(s: &str) -> Result<(u8, u8, u8), String> {
+    parse_triple(s, ',').ok_or_else(|| format!("invalid interior color: '{}'", s))
+}
@@ -0,0 +1,102 @@
+use num::Complex;
+use std::str::FromStr;
+
+/// The family of escape-time fractal to render.
+///
+/// Each variant describes how to derive the next iterate `z` from the
+/// previous one and the point `c`; see `FractalKind::step`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FractalKind {
+    /// The classic Mandelbrot set: `z = z*z + c`.
+    Mandelbrot,
+    /// Generalized Mandelbrot set using `z = z.powu(d) + c`.
+    Multibrot(u32),
+    /// Fold the iterate to absolute values before squaring.
+    BurningShip,
+    /// Conjugate the iterate before squaring.
+    Tricorn,
+}
+
+impl FractalKind {
+    /// Compute the next iterate of this fractal's recurrence.
+    pub fn step(&self, z: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        match *self {
+            FractalKind::Mandelbrot => z * z + c,
+            FractalKind::Multibrot(d) => z.powu(d) + c,
+            FractalKind::BurningShip => {
+                let z = Complex {
+                    re: z.re.abs(),
+                    im: z.im.abs(),
+                };
+                z * z + c
+            }
+            FractalKind::Tricorn => {
+                let z = z.conj();
+                z * z + c
+            }
+        }
+    }
+}
+
+/// Parse a fractal name, e.g. `"mandelbrot"`, `"burningship"`, `"tricorn"`,
+/// or `"multibrot:<degree>"` (e.g. `"multibrot:3"`).
+impl FromStr for FractalKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        match parts.next().unwrap().to_lowercase().as_str() {
+            "mandelbrot" => Ok(FractalKind::Mandelbrot),
+            "burningship" => Ok(FractalKind::BurningShip),
+            "tricorn" => Ok(FractalKind::Tricorn),
+            "multibrot" => {
+                let degree = parts
+                    .next()
+                    .ok_or_else(|| "multibrot requires a degree, e.g. 'multibrot:3'".to_string())?
+                    .parse::<u32>()
+                    .map_err(|e| format!("invalid multibrot degree: {}", e))?;
+                Ok(FractalKind::Multibrot(degree))
+            }
+            other => Err(format!("unknown fractal kind: '{}'", other)),
+        }
+    }
+}
+
+#[test]
+fn test_step_mandelbrot() {
+    let z = Complex { re: 1.0, im: 2.0 };
+    let c = Complex { re: 0.5, im: -0.5 };
+    assert_eq!(FractalKind::Mandelbrot.step(z, c), z * z + c);
+}
+
+#[test]
+fn test_step_multibrot_matches_powu() {
+    let z = Complex { re: 1.0, im: 2.0 };
+    let c = Complex { re: 0.5, im: -0.5 };
+    assert_eq!(FractalKind::Multibrot(3).step(z, c), z.powu(3) + c);
+}
+
+#[test]
+fn test_step_burning_ship_folds_signs_before_squaring() {
+    let z = Complex { re: -1.0, im: -2.0 };
+    let c = Complex { re: 0.5, im: -0.5 };
+    let folded = Complex { re: 1.0, im: 2.0 };
+    assert_eq!(FractalKind::BurningShip.step(z, c), folded * folded + c);
+}
+
+#[test]
+fn test_step_tricorn_conjugates_before_squaring() {
+    let z = Complex { re: 1.0, im: 2.0 };
+    let c = Complex { re: 0.5, im: -0.5 };
+    assert_eq!(FractalKind::Tricorn.step(z, c), z.conj() * z.conj() + c);
+}
+
+#[test]
+fn test_parse_fractal_kind() {
+    assert_eq!("mandelbrot".parse(), Ok(FractalKind::Mandelbrot));
+    assert_eq!("BurningShip".parse(), Ok(FractalKind::BurningShip));
+    assert_eq!("tricorn".parse(), Ok(FractalKind::Tricorn));
+    assert_eq!("multibrot:3".parse(), Ok(FractalKind::Multibrot(3)));
+    assert!("multibrot".parse::<FractalKind>().is_err());
+    assert!("nonsense".parse::<FractalKind>().is_err());
+}
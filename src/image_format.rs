@@ -0,0 +1,142 @@
+use std::convert::TryInto;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+
+/// An output image container, inferred from a filename's extension or
+/// selected explicitly with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Png,
+    /// Binary NetPBM color image (`P6`).
+    Ppm,
+    /// Binary NetPBM grayscale image (`P5`), taking the red channel of the
+    /// (assumed grayscale) input as its intensity.
+    Pgm,
+    Bmp,
+}
+
+impl OutputFormat {
+    /// Infer the format from a filename's extension, e.g. `"out.ppm"` -> `Ppm`.
+    pub fn from_extension(filename: &str) -> Option<Self> {
+        let extension = filename.rsplit('.').next()?;
+        extension.to_lowercase().parse().ok()
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "png" => Ok(OutputFormat::Png),
+            "ppm" => Ok(OutputFormat::Ppm),
+            "pgm" => Ok(OutputFormat::Pgm),
+            "bmp" => Ok(OutputFormat::Bmp),
+            other => Err(format!("unknown image format: '{}'", other)),
+        }
+    }
+}
+
+/// Write a binary PPM (`P6`) image: RGB pixels, top-down, left-to-right.
+pub fn write_ppm(filename: &str, pixels: &[u8], bounds: (usize, usize)) -> io::Result<()> {
+    let mut output = File::create(filename)?;
+    write!(output, "P6\n{} {}\n255\n", bounds.0, bounds.1)?;
+    output.write_all(pixels)
+}
+
+/// Write a binary PGM (`P5`) image, taking every red byte of an RGB buffer
+/// as the grayscale intensity.
+pub fn write_pgm(filename: &str, pixels: &[u8], bounds: (usize, usize)) -> io::Result<()> {
+    let mut output = File::create(filename)?;
+    write!(output, "P5\n{} {}\n255\n", bounds.0, bounds.1)?;
+    let gray: Vec<u8> = pixels.iter().step_by(3).cloned().collect();
+    output.write_all(&gray)
+}
+
+/// Write a 24-bit uncompressed BMP image.
+pub fn write_bmp(filename: &str, pixels: &[u8], bounds: (usize, usize)) -> io::Result<()> {
+    let (width, height) = (bounds.0 as u32, bounds.1 as u32);
+    let row_size = (width * 3).div_ceil(4) * 4;
+    let pixel_data_size = row_size * height;
+    let file_size = 54 + pixel_data_size;
+
+    let mut output = File::create(filename)?;
+
+    // BITMAPFILEHEADER
+    output.write_all(b"BM")?;
+    output.write_all(&file_size.to_le_bytes())?;
+    output.write_all(&0u16.to_le_bytes())?; // reserved1
+    output.write_all(&0u16.to_le_bytes())?; // reserved2
+    output.write_all(&54u32.to_le_bytes())?; // pixel data offset
+
+    // BITMAPINFOHEADER
+    output.write_all(&40u32.to_le_bytes())?; // header size
+    output.write_all(&(width as i32).to_le_bytes())?;
+    output.write_all(&(height as i32).to_le_bytes())?; // positive => bottom-up
+    output.write_all(&1u16.to_le_bytes())?; // planes
+    output.write_all(&24u16.to_le_bytes())?; // bits per pixel
+    output.write_all(&0u32.to_le_bytes())?; // compression (none)
+    output.write_all(&pixel_data_size.to_le_bytes())?;
+    output.write_all(&0i32.to_le_bytes())?; // x pixels per meter
+    output.write_all(&0i32.to_le_bytes())?; // y pixels per meter
+    output.write_all(&0u32.to_le_bytes())?; // colors used
+    output.write_all(&0u32.to_le_bytes())?; // important colors
+
+    let padding = vec![0u8; (row_size - width * 3) as usize];
+    for row in (0..bounds.1).rev() {
+        for column in 0..bounds.0 {
+            let i = (row * bounds.0 + column) * 3;
+            // BMP stores pixels as BGR, not RGB.
+            output.write_all(&[pixels[i + 2], pixels[i + 1], pixels[i]])?;
+        }
+        output.write_all(&padding)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_write_ppm_header_and_pixels() {
+    let path = std::env::temp_dir().join("mandelbrot_test_write_ppm.ppm");
+    let pixels = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+    write_ppm(path.to_str().unwrap(), &pixels, (2, 2)).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut expected = b"P6\n2 2\n255\n".to_vec();
+    expected.extend_from_slice(&pixels);
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn test_write_pgm_header_and_pixels() {
+    let path = std::env::temp_dir().join("mandelbrot_test_write_pgm.pgm");
+    let pixels = [10, 10, 10, 20, 20, 20, 30, 30, 30, 40, 40, 40];
+    write_pgm(path.to_str().unwrap(), &pixels, (2, 2)).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut expected = b"P5\n2 2\n255\n".to_vec();
+    expected.extend_from_slice(&[10, 20, 30, 40]);
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn test_write_bmp_header_and_pixel_order() {
+    let path = std::env::temp_dir().join("mandelbrot_test_write_bmp.bmp");
+    let pixels = [255, 0, 0]; // a single red pixel
+    write_bmp(path.to_str().unwrap(), &pixels, (1, 1)).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(&bytes[0..2], b"BM");
+    assert_eq!(u32::from_le_bytes(bytes[2..6].try_into().unwrap()), 58);
+    assert_eq!(u32::from_le_bytes(bytes[10..14].try_into().unwrap()), 54);
+    assert_eq!(bytes.len(), 58);
+    // BMP stores pixels bottom-up as BGR, so a red pixel becomes [0, 0, 255],
+    // followed by one byte of row padding (3 bytes rounds up to 4).
+    assert_eq!(&bytes[54..57], &[0, 0, 255]);
+    assert_eq!(bytes[57], 0);
+}
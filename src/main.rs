@@ -1,18 +1,33 @@
+extern crate clap;
 extern crate crossbeam;
+extern crate indicatif;
 extern crate num;
 extern crate probability;
+extern crate rand;
+extern crate rayon;
 extern crate slice_of_array;
 
-use ::slice_of_array::prelude::*;
+use clap::Parser;
 use image::png::PNGEncoder;
 use image::ColorType;
+use indicatif::ProgressBar;
+use indicatif::ProgressStyle;
 use num::Complex;
-use probability::prelude::Continuous;
-use probability::prelude::Gaussian;
+use rayon::prelude::*;
+use slice_of_array::prelude::*;
 use std::fs::File;
-use std::f64;
 use std::str::FromStr;
 
+mod buddhabrot;
+mod cli;
+mod fractal;
+mod image_format;
+mod palette;
+
+use fractal::FractalKind;
+use image_format::OutputFormat;
+use palette::Palette;
+
 /// Try to determina if 'c' is in the Mandelbrot set, using at most 'limit' iterations to decide.
 ///
 /// If 'c' is not a mebmer, return 'Some(i)', where 'i' is the number of iterations it took for 'c'
@@ -20,10 +35,10 @@ use std::str::FromStr;
 /// If'c' seems to be a member (more precisely, if we reached the iteration limit without being able to prove that 'c'
 /// is not a member), return 'None'.
 
-fn escape_time(c: Complex<f64>, limit: u32) -> Option<u32> {
+fn escape_time(kind: FractalKind, c: Complex<f64>, limit: u32) -> Option<u32> {
     let mut z = Complex { re: 0.0, im: 0.0 };
     for i in 0..limit {
-        z = z * z + c;
+        z = kind.step(z, c);
         if z.norm_sqr() > 4.0 {
             return Some(i);
         }
@@ -31,6 +46,43 @@ fn escape_time(c: Complex<f64>, limit: u32) -> Option<u32> {
     None
 }
 
+/// Like `escape_time`, but returns a continuous (fractional) iteration count
+/// instead of an integer, eliminating the banding that comes from coloring
+/// by a whole iteration number.
+///
+/// Uses a much larger bailout radius (`2^16` instead of `2`) than
+/// `escape_time`, since the normalized iteration count estimate below only
+/// converges once `z` is well past the boundary of the set; a few extra
+/// iterations past bailout meaningfully improve the estimate's accuracy.
+fn escape_time_smooth(kind: FractalKind, c: Complex<f64>, limit: u32) -> Option<f64> {
+    const BAILOUT: f64 = 65536.0; // 2^16
+    let mut z = Complex { re: 0.0, im: 0.0 };
+    for i in 0..limit {
+        z = kind.step(z, c);
+        if z.norm_sqr() > BAILOUT * BAILOUT {
+            let mu = i as f64 + 1.0 - (z.norm().ln()).ln() / 2f64.ln();
+            return Some(mu);
+        }
+    }
+    None
+}
+
+#[test]
+fn test_escape_time_smooth() {
+    // c = 0 never leaves the bailout radius: it's the center of the set.
+    assert_eq!(
+        escape_time_smooth(FractalKind::Mandelbrot, Complex { re: 0.0, im: 0.0 }, 100),
+        None
+    );
+
+    // c = 2 escapes within a handful of iterations (|z| passes 2^16 on the
+    // fifth); the smooth count should land just past the first iteration,
+    // not jump to some much larger or negative value.
+    let mu = escape_time_smooth(FractalKind::Mandelbrot, Complex { re: 2.0, im: 0.0 }, 100)
+        .expect("c = 2 should escape");
+    assert!((0.0..2.0).contains(&mu));
+}
+
 /// Parse the string `s` as a coordinate pair, like `"400x600"` or `"1.0,0.5"`.
 ///
 /// Specifically, `s` should have the form <left><sep><right>, where <sep> is
@@ -60,6 +112,28 @@ fn test_parse_pair() {
     assert_eq!(parse_pair::<f64>("0.5x1.5", 'x'), Some((0.5, 1.5)));
 }
 
+/// Parse the string `s` as three `separator`-delimited values, like `"40,40,40"`.
+///
+/// Returns `None` if `s` doesn't have exactly three `separator`-delimited
+/// fields that all parse via `T::from_str`.
+fn parse_triple<T: FromStr>(s: &str, separator: char) -> Option<(T, T, T)> {
+    let mut parts = s.splitn(3, separator);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(a), Some(b), Some(c)) => match (T::from_str(a), T::from_str(b), T::from_str(c)) {
+            (Ok(a), Ok(b), Ok(c)) => Some((a, b, c)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[test]
+fn test_parse_triple() {
+    assert_eq!(parse_triple::<u8>("40,40,40", ','), Some((40, 40, 40)));
+    assert_eq!(parse_triple::<u8>("40,40", ','), None);
+    assert_eq!(parse_triple::<u8>("", ','), None);
+}
+
 /// Parse a pair of floating-point number separated by a comma as a comples number
 fn parse_complex(s: &str) -> Option<Complex<f64>> {
     match parse_pair(s, ',') {
@@ -104,6 +178,51 @@ fn pixel_to_point(
     }
 }
 
+/// The inverse of `pixel_to_point`: given a point on the complex plane,
+/// return the pixel it falls into, or `None` if it lies outside `bounds`.
+fn point_to_pixel(
+    bounds: (usize, usize),
+    point: Complex<f64>,
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+) -> Option<(usize, usize)> {
+    let (width, height) = (
+        lower_right.re - upper_left.re,
+        upper_left.im - lower_right.im,
+    );
+
+    let column = (point.re - upper_left.re) / width * bounds.0 as f64;
+    let row = (upper_left.im - point.im) / height * bounds.1 as f64;
+
+    if column < 0.0 || row < 0.0 || column >= bounds.0 as f64 || row >= bounds.1 as f64 {
+        return None;
+    }
+
+    Some((column as usize, row as usize))
+}
+
+#[test]
+fn test_point_to_pixel() {
+    assert_eq!(
+        point_to_pixel(
+            (100, 100),
+            Complex { re: -0.5, im: -0.5 },
+            Complex { re: -1.0, im: 1.0 },
+            Complex { re: 1.0, im: -1.0 }
+        ),
+        Some((25, 75))
+    );
+    assert_eq!(
+        point_to_pixel(
+            (100, 100),
+            Complex { re: 5.0, im: 5.0 },
+            Complex { re: -1.0, im: 1.0 },
+            Complex { re: 1.0, im: -1.0 }
+        ),
+        None
+    );
+}
+
 #[test]
 fn test_pixel_to_point() {
     assert_eq!(
@@ -117,133 +236,168 @@ fn test_pixel_to_point() {
     );
 }
 
-/// Calculate the RGB value of the mandelbrot set.
-///
-/// The RGB value is calculated by three gaussian fits. For each color of red,
-/// green and blue one gaussian is calculated. Each input value creates the superset
-/// of the three gaussians and therefore three RGB colors
-///
-/// The mean value of the three gaussian are the limit divided by three and shifted to the left
-/// by a sixth of the mean. 
-/// The variance is calculated by Full Width Half Mean (FWHM) approxiamtion.
-fn calculate_rgb(limit: u32, value: f64) -> (u8, u8, u8) {
-    let fwhm = limit as f64 / 2.0;
-    let sigma = fwhm / 2.3548; // see Gaussian Full Width Half Maximum Approximation
-
-    let base_point_blue = limit as f64 / 6.0;
-    let base_point_green = base_point_blue + limit as f64 / 3.0;
-    let base_point_red = base_point_green + limit as f64 / 3.0;
-
-    let red_gaussian = Gaussian::new(base_point_red, sigma);
-    let green_gaussian = Gaussian::new(base_point_green, sigma);
-    let blue_gaussian = Gaussian::new(base_point_blue, sigma);
-
-    let scale = limit as f64 * (2.0 * f64::consts::PI * sigma.powi(2)).sqrt();
-
-    let red = scale * red_gaussian.density(value);
-    let green = scale * green_gaussian.density(value);
-    let blue = scale * blue_gaussian.density(value);
-
-    (red as u8, green as u8, blue as u8)
-}
-
-#[test]
-fn test_get_rgb() {
-    assert_eq!(calculate_rgb(255, 42.5), (1, 74, 255));
-    assert_eq!(calculate_rgb(255, 127.5), (74, 255, 74));
-    assert_eq!(calculate_rgb(255, 212.5), (255, 74, 1));
-    assert_eq!(calculate_rgb(255, 85.0), (15, 187, 187));
+/// The escape-time parameters `render` needs beyond the pixel buffer and the
+/// rectangle it covers: which fractal family to iterate, how many iterations
+/// to allow, whether to use smooth coloring, and how to color the result.
+struct RenderOptions {
+    /// Which fractal family to iterate.
+    kind: FractalKind,
+    /// Maximum number of escape-time iterations per pixel.
+    limit: u32,
+    /// Use continuous (smooth) coloring instead of integer iteration counts.
+    smooth: bool,
+    /// Coloring scheme applied to escaped points.
+    palette: Palette,
+    /// Color for points that never escape.
+    interior_color: (u8, u8, u8),
 }
 
 /// Render a rectangle of the Mandelbrot set into a buffer of pixels.
 ///
-/// The `bounds` argument gives the width and height of the buffer `pixels`,
-/// which holds one grayscale pixel per byte. The `upper_left` and `lower_right`
-/// arguments specify points on the complex plane corresponding to the upper-
-/// left and lower-right corners of the pixel buffer.
+/// The `bounds` argument gives the width and height of the buffer `pixels`.
+/// The `upper_left` and `lower_right` arguments specify points on the complex
+/// plane corresponding to the upper-left and lower-right corners of the pixel
+/// buffer. `options` selects the fractal family, iteration limit, coloring
+/// scheme, and interior color to render with.
 fn render(
     pixels: &mut [(u8, u8, u8)],
     bounds: (usize, usize),
     upper_left: Complex<f64>,
     lower_right: Complex<f64>,
+    options: &RenderOptions,
 ) {
     assert!(pixels.len() == bounds.0 * bounds.1);
-    let limit = 255;
     for row in 0..bounds.1 {
         for column in 0..bounds.0 {
             let point = pixel_to_point(bounds, (column, row), upper_left, lower_right);
-            pixels[row * bounds.0 + column] = match escape_time(point, limit) {
-                None => (40, 40, 40),
-                Some(count) => calculate_rgb(limit, count as f64),
+            pixels[row * bounds.0 + column] = if options.smooth {
+                match escape_time_smooth(options.kind, point, options.limit) {
+                    None => options.interior_color,
+                    Some(mu) => options.palette.color(options.limit, mu),
+                }
+            } else {
+                match escape_time(options.kind, point, options.limit) {
+                    None => options.interior_color,
+                    Some(count) => options.palette.color(options.limit, count as f64),
+                }
             };
         }
     }
 }
 
+/// Write an RGB image buffer to `filename`, in `format` if given, or
+/// otherwise whatever format `filename`'s extension implies.
 fn write_image(
     filename: &str,
     pixels: &[u8],
     bounds: (usize, usize),
+    format: Option<OutputFormat>,
 ) -> Result<(), std::io::Error> {
-    let output = File::create(filename)?;
-    let encoder = PNGEncoder::new(output);
-
-    encoder.encode(&pixels, bounds.0 as u32, bounds.1 as u32, ColorType::RGB(8))?;
-    Ok(())
-}
-
-use std::io::Write;
-
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-
-    if args.len() != 5 {
-        writeln!(
-            std::io::stderr(),
-            "Usage: mandelbrot FILE PIXELS UPPERLEFT LOWERRIGHT"
-        )
-        .unwrap();
-
-        writeln!(
-            std::io::stderr(),
-            "Example: {} mandel.png 1000x750 -1.20,0.35 -1.0,0.20",
-            args[0]
-        )
-        .unwrap();
-
-        std::process::exit(1);
+    let format = format
+        .or_else(|| OutputFormat::from_extension(filename))
+        .unwrap_or(OutputFormat::Png);
+
+    match format {
+        OutputFormat::Png => {
+            let output = File::create(filename)?;
+            let encoder = PNGEncoder::new(output);
+            encoder.encode(&pixels, bounds.0 as u32, bounds.1 as u32, ColorType::RGB(8))?;
+            Ok(())
+        }
+        OutputFormat::Ppm => image_format::write_ppm(filename, pixels, bounds),
+        OutputFormat::Pgm => image_format::write_pgm(filename, pixels, bounds),
+        OutputFormat::Bmp => image_format::write_bmp(filename, pixels, bounds),
     }
+}
 
-    let bounds = parse_pair::<usize>(&args[2], 'x').expect("error parsing image dimensions");
-    let upper_left = parse_complex(&args[3]).expect("error parsing upper left corner point");
-    let lower_right = parse_complex(&args[4]).expect("error parsing lower right corner point");
+fn run_render(args: cli::RenderArgs) {
+    let bounds = args.dimensions;
+    let (upper_left, lower_right) = args.region;
+    let fractal_kind = args.fractal;
+    let max_iterations = args.max_iterations;
+    let smooth = args.smooth;
+    let palette = args.palette;
+    let interior_color = args.interior_color;
 
     let mut pixels = vec![(0, 0, 0); bounds.0 * bounds.1];
 
-    let threads = 8;
-    let rows_per_band = bounds.1 / threads + 1;
-
-    {
-        let bands: Vec<&mut [(u8, u8, u8)]> = pixels.chunks_mut(rows_per_band * bounds.0).collect();
+    let progress = ProgressBar::new(bounds.1 as u64);
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template("{bar:40} {pos}/{len} rows ({eta} remaining)")
+            .unwrap(),
+    );
 
-        crossbeam::scope(|spawner| {
-            for (i, band) in bands.into_iter().enumerate() {
-                let top = rows_per_band * i;
-                let height = band.len() / bounds.0;
-                let band_bounds = (bounds.0, height);
-                let band_upper_left = pixel_to_point(bounds, (0, top), upper_left, lower_right);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build()
+        .expect("error building thread pool");
+
+    let options = RenderOptions {
+        kind: fractal_kind,
+        limit: max_iterations,
+        smooth,
+        palette,
+        interior_color,
+    };
+
+    pool.install(|| {
+        pixels
+            .par_chunks_mut(bounds.0)
+            .enumerate()
+            .for_each(|(row, band)| {
+                let band_upper_left = pixel_to_point(bounds, (0, row), upper_left, lower_right);
                 let band_lower_right =
-                    pixel_to_point(bounds, (bounds.0, top + height), upper_left, lower_right);
-
-                spawner.spawn(move || render(band, band_bounds, band_upper_left, band_lower_right));
-            }
-        });
-    }
+                    pixel_to_point(bounds, (bounds.0, row + 1), upper_left, lower_right);
+
+                render(
+                    band,
+                    (bounds.0, 1),
+                    band_upper_left,
+                    band_lower_right,
+                    &options,
+                );
+                progress.inc(1);
+            });
+    });
+    progress.finish_with_message("render complete");
 
     let rgb_image = pixels
         .iter()
         .map(|&x| [x.0 as u8, x.1 as u8, x.2 as u8])
         .collect::<Vec<_>>();
 
-    write_image(&args[1], rgb_image.flat(), bounds).expect("error writing PNG file");
+    write_image(&args.file, rgb_image.flat(), bounds, args.format)
+        .expect("error writing image file");
+}
+
+fn run_buddhabrot(args: cli::BuddhabrotArgs) {
+    let bounds = args.dimensions;
+    let (upper_left, lower_right) = args.region;
+
+    let histogram = buddhabrot::accumulate(
+        bounds,
+        upper_left,
+        lower_right,
+        args.samples,
+        args.max_iterations,
+        args.threads,
+    );
+    let gray = buddhabrot::histogram_to_pixels(&histogram);
+    let rgb_image = gray
+        .iter()
+        .map(|&brightness| [brightness, brightness, brightness])
+        .collect::<Vec<_>>();
+
+    write_image(&args.file, rgb_image.flat(), bounds, args.format)
+        .expect("error writing image file");
+}
+
+fn main() {
+    let cli = cli::Cli::parse();
+
+    match cli.mode {
+        cli::Mode::Render(args) => run_render(args),
+        cli::Mode::Buddhabrot(args) => run_buddhabrot(args),
+    }
 }
@@ -0,0 +1,173 @@
+use probability::prelude::Continuous;
+use probability::prelude::Gaussian;
+use std::f64;
+use std::str::FromStr;
+
+/// A coloring scheme for escaped points, selectable from the CLI.
+///
+/// `color` maps an escape-time `value` (an integer iteration count, or a
+/// continuous one from `escape_time_smooth`) and the iteration `limit` it
+/// was measured against onto an RGB triple.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Palette {
+    /// The original three-Gaussian-curve mapping.
+    Gaussian,
+    /// Cyclic hue sweep: iteration count maps to hue 0-360, HSL -> RGB.
+    Hsl,
+    /// A "fire" gradient interpolated between a handful of control colors.
+    Fire,
+    /// An "ocean" gradient interpolated between a handful of control colors.
+    Ocean,
+}
+
+impl Palette {
+    pub fn color(&self, limit: u32, value: f64) -> (u8, u8, u8) {
+        match *self {
+            Palette::Gaussian => gaussian_rgb(limit, value),
+            Palette::Hsl => hsl_rgb(limit, value),
+            Palette::Fire => gradient_rgb(FIRE_STOPS, limit, value),
+            Palette::Ocean => gradient_rgb(OCEAN_STOPS, limit, value),
+        }
+    }
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "gaussian" => Ok(Palette::Gaussian),
+            "hsl" => Ok(Palette::Hsl),
+            "fire" => Ok(Palette::Fire),
+            "ocean" => Ok(Palette::Ocean),
+            other => Err(format!("unknown palette: '{}'", other)),
+        }
+    }
+}
+
+/// Calculate the RGB value of the mandelbrot set.
+///
+/// The RGB value is calculated by three gaussian fits. For each color of red,
+/// green and blue one gaussian is calculated. Each input value creates the superset
+/// of the three gaussians and therefore three RGB colors
+///
+/// The mean value of the three gaussian are the limit divided by three and shifted to the left
+/// by a sixth of the mean.
+/// The variance is calculated by Full Width Half Mean (FWHM) approxiamtion.
+fn gaussian_rgb(limit: u32, value: f64) -> (u8, u8, u8) {
+    let fwhm = limit as f64 / 2.0;
+    let sigma = fwhm / 2.3548; // see Gaussian Full Width Half Maximum Approximation
+
+    let base_point_blue = limit as f64 / 6.0;
+    let base_point_green = base_point_blue + limit as f64 / 3.0;
+    let base_point_red = base_point_green + limit as f64 / 3.0;
+
+    let red_gaussian = Gaussian::new(base_point_red, sigma);
+    let green_gaussian = Gaussian::new(base_point_green, sigma);
+    let blue_gaussian = Gaussian::new(base_point_blue, sigma);
+
+    let scale = limit as f64 * (2.0 * f64::consts::PI * sigma.powi(2)).sqrt();
+
+    let red = scale * red_gaussian.density(value);
+    let green = scale * green_gaussian.density(value);
+    let blue = scale * blue_gaussian.density(value);
+
+    (red as u8, green as u8, blue as u8)
+}
+
+#[test]
+fn test_gaussian_rgb() {
+    assert_eq!(gaussian_rgb(255, 42.5), (1, 74, 255));
+    assert_eq!(gaussian_rgb(255, 127.5), (74, 255, 74));
+    assert_eq!(gaussian_rgb(255, 212.5), (255, 74, 1));
+    assert_eq!(gaussian_rgb(255, 85.0), (15, 187, 187));
+}
+
+/// Map `value` cyclically onto a hue and convert the resulting HSL color
+/// (full saturation, mid lightness) to RGB.
+fn hsl_rgb(limit: u32, value: f64) -> (u8, u8, u8) {
+    let hue = (value / limit as f64).fract() * 360.0;
+    hsl_to_rgb(hue, 1.0, 0.5)
+}
+
+/// Convert an HSL color (`hue` in degrees, `saturation` and `lightness` in
+/// `0.0..=1.0`) to 8-bit RGB.
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h_prime = hue / 60.0;
+    let x = c * (1.0 - (h_prime % 2.0 - 1.0).abs());
+    let (r1, g1, b1) = match h_prime as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = lightness - c / 2.0;
+
+    (
+        ((r1 + m) * 255.0) as u8,
+        ((g1 + m) * 255.0) as u8,
+        ((b1 + m) * 255.0) as u8,
+    )
+}
+
+#[test]
+fn test_hsl_to_rgb() {
+    assert_eq!(hsl_to_rgb(0.0, 1.0, 0.5), (255, 0, 0));
+    assert_eq!(hsl_to_rgb(120.0, 1.0, 0.5), (0, 255, 0));
+    assert_eq!(hsl_to_rgb(240.0, 1.0, 0.5), (0, 0, 255));
+}
+
+/// Control colors for the "fire" gradient, from cool to hot.
+const FIRE_STOPS: &[(f64, (u8, u8, u8))] = &[
+    (0.0, (0, 0, 0)),
+    (0.4, (128, 0, 0)),
+    (0.7, (255, 128, 0)),
+    (1.0, (255, 255, 200)),
+];
+
+/// Control colors for the "ocean" gradient, from deep to shallow.
+const OCEAN_STOPS: &[(f64, (u8, u8, u8))] = &[
+    (0.0, (0, 8, 32)),
+    (0.5, (0, 80, 160)),
+    (1.0, (180, 230, 255)),
+];
+
+/// Linearly interpolate between the nearest two control colors in `stops`
+/// for a normalized iteration fraction of `value` against `limit`.
+fn gradient_rgb(stops: &[(f64, (u8, u8, u8))], limit: u32, value: f64) -> (u8, u8, u8) {
+    let t = (value / limit as f64).clamp(0.0, 1.0);
+
+    let mut lower = stops[0];
+    let mut upper = stops[stops.len() - 1];
+    for window in stops.windows(2) {
+        if t >= window[0].0 && t <= window[1].0 {
+            lower = window[0];
+            upper = window[1];
+            break;
+        }
+    }
+
+    let span = upper.0 - lower.0;
+    let local_t = if span > 0.0 {
+        (t - lower.0) / span
+    } else {
+        0.0
+    };
+
+    let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * local_t) as u8;
+
+    (
+        lerp(lower.1 .0, upper.1 .0),
+        lerp(lower.1 .1, upper.1 .1),
+        lerp(lower.1 .2, upper.1 .2),
+    )
+}
+
+#[test]
+fn test_gradient_rgb_endpoints() {
+    assert_eq!(gradient_rgb(FIRE_STOPS, 100, 0.0), (0, 0, 0));
+    assert_eq!(gradient_rgb(FIRE_STOPS, 100, 100.0), (255, 255, 200));
+}